@@ -0,0 +1,38 @@
+pub struct Comparator {
+  is_high: bool,
+}
+
+impl Comparator {
+  pub fn new() -> Self {
+    Self { is_high: false }
+  }
+
+  /// Goes high when the input exceeds the threshold and low when it falls below it.
+  /// The hysteresis is split evenly around the threshold, so a noisy input doesn't chatter.
+  pub fn process(&mut self, input: f32, threshold: f32, hysteresis: f32) -> bool {
+    let half_hysteresis = hysteresis * 0.5;
+    if self.is_high {
+      self.is_high = input >= threshold - half_hysteresis;
+    } else {
+      self.is_high = input > threshold + half_hysteresis;
+    }
+    self.is_high
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Comparator;
+
+  #[test]
+  fn should_switch_with_hysteresis() {
+    let mut comparator = Comparator::new();
+    assert!(!comparator.process(0.25, 0.2, 0.2));
+    assert!(comparator.process(0.35, 0.2, 0.2));
+    assert!(comparator.process(0.15, 0.2, 0.2));
+    assert!(!comparator.process(0.05, 0.2, 0.2));
+
+    assert!(comparator.process(0.25, 0.2, 0.));
+    assert!(!comparator.process(0.15, 0.2, 0.));
+  }
+}
@@ -0,0 +1,66 @@
+use crate::{
+  params::{LinearSmooth, Smoother},
+  shared::float_ext::FloatExt,
+};
+
+pub struct FadeIn {
+  sample_rate: f32,
+  ramp: LinearSmooth,
+  elapsed: f32,
+  is_delaying: bool,
+}
+
+impl FadeIn {
+  pub fn new(sample_rate: f32) -> Self {
+    let mut ramp = LinearSmooth::new(sample_rate, 12.);
+    ramp.reset(1.);
+
+    Self {
+      sample_rate,
+      ramp,
+      elapsed: 0.,
+      is_delaying: false,
+    }
+  }
+
+  pub fn restart(&mut self) {
+    self.ramp.reset(0.);
+    self.elapsed = 0.;
+    self.is_delaying = true;
+  }
+
+  /// Returns a gain that stays silent for the delay time and then ramps up to one in the fade time.
+  /// Both times are in milliseconds.
+  pub fn process(&mut self, delay: f32, fade: f32) -> f32 {
+    if self.is_delaying {
+      if self.elapsed < delay.mstosamps(self.sample_rate) {
+        self.elapsed += 1.;
+        return 0.;
+      }
+      self.is_delaying = false;
+      self.ramp.set_time(fade);
+      self.ramp.set_target(1.);
+    }
+    self.ramp.next()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::FadeIn;
+
+  #[test]
+  fn should_delay_and_fade_in_after_a_restart() {
+    let mut fade_in = FadeIn::new(1000.);
+    assert_eq!(fade_in.process(2., 4.), 1.);
+
+    fade_in.restart();
+    assert_eq!(fade_in.process(2., 4.), 0.);
+    assert_eq!(fade_in.process(2., 4.), 0.);
+    assert_eq!(fade_in.process(2., 4.), 0.25);
+    assert_eq!(fade_in.process(2., 4.), 0.5);
+    assert_eq!(fade_in.process(2., 4.), 0.75);
+    assert_eq!(fade_in.process(2., 4.), 1.);
+    assert_eq!(fade_in.process(2., 4.), 1.);
+  }
+}
@@ -0,0 +1,47 @@
+use crate::shared::float_ext::FloatExt;
+
+pub struct Gate {
+  sample_rate: f32,
+  remaining: f32,
+}
+
+impl Gate {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      sample_rate,
+      remaining: 0.,
+    }
+  }
+
+  /// Opens the gate on a trigger and keeps it open for the length in milliseconds.
+  /// A trigger while the gate is open restarts the length.
+  pub fn process(&mut self, trigger: bool, length: f32) -> bool {
+    if trigger {
+      self.remaining = length.mstosamps(self.sample_rate).max(1.);
+    }
+    if self.remaining > 0. {
+      self.remaining -= 1.;
+      true
+    } else {
+      false
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Gate;
+
+  #[test]
+  fn should_stay_open_for_the_gate_length() {
+    let mut gate = Gate::new(1000.);
+    assert!(!gate.process(false, 3.));
+    assert!(gate.process(true, 3.));
+    assert!(gate.process(false, 3.));
+    assert!(gate.process(false, 3.));
+    assert!(!gate.process(false, 3.));
+
+    assert!(gate.process(true, 0.));
+    assert!(!gate.process(false, 0.));
+  }
+}
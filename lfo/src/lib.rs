@@ -1,150 +1,440 @@
+mod comparator;
 mod delta;
+mod fade_in;
+mod gate;
 mod params;
 mod phasor;
+mod quantizer;
+mod ratio_outputs;
+mod rising_edge;
+mod shape_outputs;
+mod shaper;
+mod slew;
+mod spread_outputs;
 mod shared {
   pub mod float_ext;
 }
-pub use params::Params;
 use {
   crate::shared::float_ext::FloatExt,
+  comparator::Comparator,
   delta::Delta,
-  params::{LfoShape, Smoother},
+  fade_in::FadeIn,
+  gate::Gate,
+  params::{
+    FmMode, LfoShape, LinearSmooth, OutputRange, Overflow, RateMode, ShapeChange, Smoother,
+    StepMode, SyncMode,
+  },
   phasor::Phasor,
-  std::f32::consts::{PI, TAU},
+  rising_edge::RisingEdge,
+  shaper::Shaper,
+  slew::Slew,
 };
+pub use {
+  params::{Params, SmoothedParam},
+  ratio_outputs::{RatioOutputs, RATIO_OUTPUTS},
+  shape_outputs::{ShapeOutputs, SHAPE_OUTPUTS},
+  spread_outputs::{SpreadOutputs, MAX_SPREAD_OUTPUTS},
+};
+
+const SOFT_SYNC_WINDOW: f32 = 0.25;
+// fades between shapes in 20 milliseconds
+const CROSSFADE_FREQ: f32 = 50.;
+// a full curve raises the output to the power of 8 or 1/8
+const CURVE_RANGE: f32 = 2.0794415;
+const GATE_VOLTAGE: f32 = 10.;
+// soft clipping leaves the values below this level untouched
+const SOFT_CLIP_KNEE: f32 = 0.8;
+
+/// The CV inputs in volts. The depth, offset and pulse width are added to their knob values,
+/// where ten volts equal a hundred percent.
+#[derive(Clone, Copy, Default)]
+pub struct CvInputs {
+  pub reset: f32,
+  pub sync: f32,
+  pub fm: f32,
+  pub depth: f32,
+  pub offset: f32,
+  pub pulse_width: f32,
+}
+
+pub struct Outputs {
+  pub output: f32,
+  /// A gate that opens at the start of each cycle.
+  pub trigger: f32,
+  /// A gate that is high while the output exceeds the threshold.
+  pub comparator: f32,
+  /// A 0 to 10 volt ramp of the phase before the phase offset.
+  pub phase_ramp: f32,
+}
+
+/// The state of the last processed sample that the extra outputs derive their values from.
+#[derive(Default)]
+pub(crate) struct Frame {
+  pub phase: f32,
+  /// The cycles since the start of the song, including the phase offset, while following the transport.
+  pub song_cycles: Option<f64>,
+  pub trigger: bool,
+  pub is_reset: bool,
+  pub depth: f32,
+  pub offset: f32,
+  pub chance: f32,
+  pub pulse_width: f32,
+  pub symmetry: f32,
+  pub morph: f32,
+  pub fade: f32,
+  pub rise: f32,
+  pub fall: f32,
+  pub curve: f32,
+  pub output: f32,
+}
 
 pub struct Lfo {
   phasor: Phasor,
   delta: Delta,
-  is_enabled: bool,
-  origin: f32,
-  target: f32,
+  reset_edge: RisingEdge,
+  sync_edge: RisingEdge,
+  is_reset: bool,
+  shaper: Shaper,
+  shape: LfoShape,
+  previous_shape: LfoShape,
+  crossfade: LinearSmooth,
+  slew: Slew,
+  fade_in: FadeIn,
+  trigger_gate: Gate,
+  comparator: Comparator,
+  frame: Frame,
 }
 
 impl Lfo {
   pub fn new(sample_rate: f32) -> Self {
+    let mut crossfade = LinearSmooth::new(sample_rate, CROSSFADE_FREQ);
+    crossfade.reset(1.);
+
     Self {
       phasor: Phasor::new(sample_rate),
       delta: Delta::new(),
-      is_enabled: true,
-      origin: 0.5,
-      target: 0.5,
+      reset_edge: RisingEdge::new(),
+      sync_edge: RisingEdge::new(),
+      is_reset: false,
+      shaper: Shaper::new(),
+      shape: LfoShape::Sine,
+      previous_shape: LfoShape::Sine,
+      crossfade,
+      slew: Slew::new(sample_rate),
+      fade_in: FadeIn::new(sample_rate),
+      trigger_gate: Gate::new(sample_rate),
+      comparator: Comparator::new(),
+      frame: Frame {
+        fade: 1.,
+        ..Default::default()
+      },
+    }
+  }
+
+  /// Restarts the cycle and the fade in.
+  /// The next sample re-rolls the chance decision and the random targets.
+  pub fn reset_phase(&mut self) {
+    self.restart_cycle();
+    self.fade_in.restart();
+  }
+
+  pub fn process(&mut self, params: &mut Params, cv_inputs: CvInputs) -> Outputs {
+    params.start_smoothing();
+    if self.reset_edge.process(cv_inputs.reset) {
+      self.reset_phase();
+    }
+    if self.sync_edge.process(cv_inputs.sync) {
+      self.sync(params.sync_mode);
+    }
+
+    let shape = params.shape;
+    let freq = Self::modulate_freq(
+      params.freq.next(),
+      params.fm_mode,
+      cv_inputs.fm * params.fm_depth.next(),
+    );
+    let depth = (params.depth.next() + cv_inputs.depth * 0.1).clamp(-1., 1.)
+      * self
+        .fade_in
+        .process(params.delay.next(), params.fade.next());
+    let phase_offset = params.phase_offset.next();
+
+    let song_cycles = match params.rate_mode {
+      RateMode::Transport if params.transport.is_rolling() => {
+        Some(params.transport.get_cycles(params.division))
+      }
+      _ => None,
+    };
+    let phase = match song_cycles {
+      Some(cycles) => {
+        let phase = cycles.rem_euclid(1.) as f32;
+        self.phasor.reset(phase);
+        phase
+      }
+      None => self.phasor.process(freq),
+    };
+    params.transport.process();
+    // the ramp follows the phasor before the phase offset, so it can serve as a master phase
+    let phase_ramp = phase * 10.;
+    let phase = Self::wrap(phase + phase_offset);
+    // a wrapping phase jumps down by almost a full cycle, while a decreasing phase offset only nudges it
+    let is_reset = self.is_reset;
+    let trigger = self.delta.process(phase) < -0.5 || is_reset;
+    self.is_reset = false;
+    let chance = params.chance.next();
+    if trigger {
+      self.shaper.trigger(chance);
+    }
+    let trigger_gate = if self
+      .trigger_gate
+      .process(trigger, params.gate_length.next())
+    {
+      GATE_VOLTAGE
+    } else {
+      0.
+    };
+
+    // a morphing shape follows the shape knob without delay, as the morph already crossfades it
+    let shape_change = if params.shape_morph {
+      ShapeChange::Immediate
+    } else {
+      params.shape_change
+    };
+    self.change_shape(shape, shape_change, trigger);
+    self.frame = Frame {
+      phase,
+      song_cycles: song_cycles.map(|cycles| cycles + phase_offset as f64),
+      trigger,
+      is_reset,
+      depth,
+      offset: params.offset.next() + cv_inputs.offset * 0.1,
+      chance,
+      pulse_width: (params.pulse_width.next() + cv_inputs.pulse_width * 0.1).clamp(0., 1.),
+      symmetry: params.symmetry.next(),
+      morph: params.morph.next(),
+      fade: self.crossfade.next(),
+      rise: params.rise.next(),
+      fall: params.fall.next(),
+      curve: params.curve.next(),
+      output: 0.,
+    };
+
+    let value = self.get_shape_value(&self.shaper, phase, params);
+    let value = Self::map_slewed_value(&self.frame, value, &mut self.slew, params);
+    let comparator_gate =
+      if self
+        .comparator
+        .process(value, params.threshold.next(), params.hysteresis.next())
+      {
+        GATE_VOLTAGE
+      } else {
+        0.
+      };
+    let output = Self::map_volts(value, params);
+    self.frame.output = output;
+
+    Outputs {
+      output,
+      trigger: trigger_gate,
+      comparator: comparator_gate,
+      phase_ramp,
     }
   }
 
-  pub fn process(&mut self, params: &mut Params) -> f32 {
-    let Params {
-      shape,
-      offset,
+  pub(crate) fn get_frame(&self) -> &Frame {
+    &self.frame
+  }
+
+  pub(crate) fn get_shaper(&self) -> Shaper {
+    self.shaper
+  }
+
+  /// Steps the phase and mixes in the previous shape while a crossfade between shapes is running.
+  pub(crate) fn get_shape_value(&self, shaper: &Shaper, phase: f32, params: &Params) -> f32 {
+    let Frame {
       chance,
+      pulse_width,
+      symmetry,
+      morph,
+      fade,
       ..
-    } = *params;
-    let freq = params.freq.next();
-    let depth = params.depth.next();
+    } = self.frame;
+    let phase = Self::step_phase(phase, params);
+    let value = shaper.process(self.shape, phase, chance, pulse_width, symmetry, morph);
+    if fade < 1. {
+      shaper
+        .process(
+          self.previous_shape,
+          phase,
+          chance,
+          pulse_width,
+          symmetry,
+          morph,
+        )
+        .mix(value, fade)
+    } else {
+      value
+    }
+  }
 
-    let phase = self.phasor.process(freq);
-    let trigger = self.delta.process(phase) < 0.;
-    if trigger {
-      self.is_enabled = fastrand::f32() <= chance;
+  /// Turns a shape value into volts the same way as the output, with a slew of its own.
+  pub(crate) fn render(&self, value: f32, slew: &mut Slew, params: &Params) -> f32 {
+    Self::map_volts(
+      Self::map_slewed_value(&self.frame, value, slew, params),
+      params,
+    )
+  }
+
+  fn map_slewed_value(frame: &Frame, value: f32, slew: &mut Slew, params: &Params) -> f32 {
+    let value = Self::map_value(value, frame.depth, frame.offset, params);
+    Self::apply_curve(slew.process(value, frame.rise, frame.fall), frame.curve)
+  }
+
+  pub(crate) fn step_phase(phase: f32, params: &Params) -> f32 {
+    match params.step_mode {
+      StepMode::Time if params.steps > 0. => Self::quantize(phase, params.steps),
+      _ => phase,
     }
+  }
 
-    (match shape {
-      LfoShape::Sine => {
-        if !self.is_enabled {
-          return 0.;
-        }
+  fn map_value(value: f32, depth: f32, offset: f32, params: &Params) -> f32 {
+    let value = Self::apply_overflow(value * depth + offset, params.overflow);
+    match params.step_mode {
+      StepMode::Levels if params.steps > 0. => Self::quantize_levels(value, params.steps),
+      _ => value,
+    }
+  }
 
-        (phase * TAU).fast_sin()
-      }
-      LfoShape::Triangle => {
-        if !self.is_enabled {
-          return 0.;
-        }
+  fn map_volts(value: f32, params: &Params) -> f32 {
+    let volts = Self::map_output_range(value, params.output_range);
+    if params.scale_mask == 0 {
+      volts
+    } else {
+      quantizer::quantize(
+        volts,
+        params.scale_mask,
+        params.root,
+        Self::map_output_range(-1., params.output_range),
+        Self::map_output_range(1., params.output_range),
+      )
+    }
+  }
 
-        let phase = Self::wrap(phase + 0.25);
-        if phase > 0.5 {
-          (phase - 0.5) * -4. + 1.
+  /// Rounds the -1 to 1 range down to a number of evenly spaced levels. A single level holds the center.
+  fn quantize_levels(value: f32, levels: f32) -> f32 {
+    if levels <= 1. {
+      return 0.;
+    }
+    let steps = levels - 1.;
+    ((value + 1.) * 0.5 * steps).round() / steps * 2. - 1.
+  }
+
+  /// Rounds the 0 to 1 range down to a number of steps.
+  fn quantize(value: f32, steps: f32) -> f32 {
+    (value * steps).floor() / steps
+  }
+
+  /// Keeps values that exceed the -1 to 1 range within bounds.
+  fn apply_overflow(value: f32, overflow: Overflow) -> f32 {
+    match overflow {
+      Overflow::Clamp => value.clamp(-1., 1.),
+      Overflow::Fold => 1. - ((value + 1.).rem_euclid(4.) - 2.).abs(),
+      Overflow::Wrap => {
+        if value.abs() <= 1. {
+          value
         } else {
-          phase * 4. - 1.
+          (value + 1.).rem_euclid(2.) - 1.
         }
       }
-      LfoShape::SawUp => {
-        if !self.is_enabled {
-          return 0.;
-        }
+      Overflow::SoftClip => Self::soft_clip(value),
+    }
+  }
 
-        Self::wrap(phase + 0.5) * 2. - 1.
-      }
-      LfoShape::SawDown => {
-        if !self.is_enabled {
-          return 0.;
-        }
+  /// Bends the values above the knee smoothly into the -1 to 1 range, so the edges of the range are reached.
+  fn soft_clip(value: f32) -> f32 {
+    let magnitude = value.abs();
+    if magnitude <= SOFT_CLIP_KNEE {
+      return value;
+    }
 
-        Self::wrap(1.5 - phase) * 2. - 1.
-      }
-      LfoShape::Rectangle => {
-        if !self.is_enabled {
-          return 0.;
-        }
+    let width = 1. - SOFT_CLIP_KNEE;
+    let x = ((magnitude - SOFT_CLIP_KNEE) / width).min(1.);
+    // leaves the knee with the slope of the input and arrives flat at the edge of the range
+    (SOFT_CLIP_KNEE + (x + x * x - x * x * x) * width).copysign(value)
+  }
 
-        if phase > 0.5 {
-          1.
-        } else {
-          -1.
-        }
-      }
-      LfoShape::SampleAndHold => {
-        if trigger {
-          self.target = if self.is_enabled {
-            fastrand::f32() * 2. - 1.
-          } else {
-            0.
-          };
-        }
-        self.target
-      }
-      LfoShape::Random => {
-        if trigger {
-          self.origin = self.target;
-          self.target = if self.is_enabled {
-            fastrand::f32() * 2. - 1.
-          } else {
-            0.
-          };
-        }
-        self.linear_interp(phase)
-      }
-      LfoShape::CurvedRandom => {
+  /// Bends the output exponentially for positive curves and logarithmically for negative curves.
+  fn apply_curve(value: f32, curve: f32) -> f32 {
+    if curve == 0. {
+      return value;
+    }
+
+    // an exact power keeps both ends of the range in place for any curve
+    let exponent = (curve * CURVE_RANGE).exp();
+    let unipolar_value = (value + 1.) * 0.5;
+    unipolar_value.powf(exponent) * 2. - 1.
+  }
+
+  /// Maps the -1 to 1 range to voltages.
+  fn map_output_range(value: f32, output_range: OutputRange) -> f32 {
+    match output_range {
+      OutputRange::Bipolar10 => value * 10.,
+      OutputRange::Bipolar5 => value * 5.,
+      OutputRange::Unipolar10 => (value + 1.) * 5.,
+      OutputRange::Unipolar5 => (value + 1.) * 2.5,
+    }
+  }
+
+  fn modulate_freq(freq: f32, fm_mode: FmMode, fm: f32) -> f32 {
+    match fm_mode {
+      // one Hz per volt
+      FmMode::Linear => (freq + fm).max(0.),
+      // one octave per volt
+      FmMode::Exponential => freq * fm.exp2(),
+    }
+  }
+
+  fn change_shape(&mut self, shape: LfoShape, shape_change: ShapeChange, trigger: bool) {
+    if shape == self.shape {
+      return;
+    }
+
+    match shape_change {
+      ShapeChange::Immediate => self.shape = shape,
+      ShapeChange::CycleEnd => {
         if trigger {
-          self.origin = self.target;
-          self.target = if self.is_enabled {
-            fastrand::f32() * 2. - 1.
-          } else {
-            0.
-          };
+          self.shape = shape;
         }
-        self.cosine_interp(phase)
       }
-      LfoShape::Noise => {
-        if fastrand::f32() <= chance {
-          fastrand::f32() * 2. - 1.
-        } else {
-          0.
+      ShapeChange::Crossfade => {
+        // wait for a running crossfade to finish, so the output doesn't jump to the shape it fades from
+        if self.frame.fade < 1. {
+          return;
         }
+        self.previous_shape = self.shape;
+        self.shape = shape;
+        self.crossfade.reset(0.);
+        self.crossfade.set_target(1.);
       }
-    } * depth
-      + offset)
-      .clamp(-1., 1.)
-      * 20.
+    }
   }
 
-  fn linear_interp(&self, mix: f32) -> f32 {
-    self.origin + (self.target - self.origin) * mix
+  fn restart_cycle(&mut self) {
+    self.phasor.reset(0.);
+    self.is_reset = true;
   }
 
-  fn cosine_interp(&self, mix: f32) -> f32 {
-    let cosine_mix = (1. - (mix * PI).fast_cos()) * 0.5;
-    self.origin + (self.target - self.origin) * cosine_mix
+  fn sync(&mut self, sync_mode: SyncMode) {
+    match sync_mode {
+      SyncMode::Hard => self.reset_phase(),
+      SyncMode::Soft => {
+        // only pull the phase forward when it is about to wrap anyway,
+        // without a fade in, as that would fade in each cycle of a synced clock
+        if self.phasor.get_phase() >= 1. - SOFT_SYNC_WINDOW {
+          self.restart_cycle();
+        }
+      }
+    }
   }
 
   fn wrap(x: f32) -> f32 {
@@ -155,3 +445,400 @@ impl Lfo {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::{
+    params::{FmMode, OutputRange, Overflow},
+    CvInputs, Lfo, Params,
+  };
+
+  fn set_params(params: &mut Params, shape: f32, sync_mode: f32, shape_change: f32) {
+    params.set(1., shape, 1., 1., 0.);
+    params.set_rate(1., 8.);
+    params.set_sync_mode(sync_mode);
+    params.set_shape_change(shape_change);
+    params.set_scale(1., 0., 4095.);
+  }
+
+  #[test]
+  fn should_restart_the_cycle_on_a_rising_reset_edge() {
+    let mut lfo = Lfo::new(100.);
+    let mut params = Params::new(100.);
+    set_params(&mut params, 1., 1., 1.);
+
+    let first_output = lfo.process(&mut params, CvInputs::default()).output;
+    for _ in 0..37 {
+      lfo.process(&mut params, CvInputs::default());
+    }
+    assert_eq!(
+      lfo
+        .process(
+          &mut params,
+          CvInputs {
+            reset: 1.,
+            ..Default::default()
+          }
+        )
+        .output,
+      first_output
+    );
+    assert_ne!(
+      lfo
+        .process(
+          &mut params,
+          CvInputs {
+            reset: 1.,
+            ..Default::default()
+          }
+        )
+        .output,
+      first_output
+    );
+  }
+
+  #[test]
+  fn should_restart_the_fade_in_on_a_hard_sync() {
+    let mut lfo = Lfo::new(100.);
+    let mut params = Params::new(100.);
+    set_params(&mut params, 5., 1., 1.);
+    params.set_fade_in(50., 0.);
+
+    assert_eq!(lfo.process(&mut params, CvInputs::default()).output, -10.);
+    let sync = CvInputs {
+      sync: 1.,
+      ..Default::default()
+    };
+    assert_eq!(lfo.process(&mut params, sync).output, 0.);
+    for _ in 0..4 {
+      assert_eq!(lfo.process(&mut params, CvInputs::default()).output, 0.);
+    }
+    assert_eq!(lfo.process(&mut params, CvInputs::default()).output, -10.);
+  }
+
+  #[test]
+  fn should_only_soft_sync_near_the_end_of_the_cycle() {
+    let mut lfo = Lfo::new(100.);
+    let mut params = Params::new(100.);
+    set_params(&mut params, 1., 2., 1.);
+
+    let first_output = lfo.process(&mut params, CvInputs::default()).output;
+    for _ in 0..49 {
+      lfo.process(&mut params, CvInputs::default());
+    }
+    assert_ne!(
+      lfo
+        .process(
+          &mut params,
+          CvInputs {
+            sync: 1.,
+            ..Default::default()
+          }
+        )
+        .output,
+      first_output
+    );
+    for _ in 0..40 {
+      lfo.process(&mut params, CvInputs::default());
+    }
+    assert_eq!(
+      lfo
+        .process(
+          &mut params,
+          CvInputs {
+            sync: 1.,
+            ..Default::default()
+          }
+        )
+        .output,
+      first_output
+    );
+  }
+
+  #[test]
+  fn should_defer_shape_changes_to_the_end_of_the_cycle() {
+    let mut lfo = Lfo::new(100.);
+    let mut sine_lfo = Lfo::new(100.);
+    let mut params = Params::new(100.);
+    let mut sine_params = Params::new(100.);
+    set_params(&mut params, 1., 1., 2.);
+    set_params(&mut sine_params, 1., 1., 2.);
+
+    for _ in 0..50 {
+      lfo.process(&mut params, CvInputs::default());
+      sine_lfo.process(&mut sine_params, CvInputs::default());
+    }
+    set_params(&mut params, 5., 1., 2.);
+    for _ in 0..50 {
+      assert_eq!(
+        lfo.process(&mut params, CvInputs::default()).output,
+        sine_lfo
+          .process(&mut sine_params, CvInputs::default())
+          .output
+      );
+    }
+    assert_eq!(lfo.process(&mut params, CvInputs::default()).output, -10.);
+  }
+
+  #[test]
+  fn should_output_the_phase_as_a_ramp() {
+    let mut lfo = Lfo::new(100.);
+    let mut params = Params::new(100.);
+    set_params(&mut params, 1., 1., 1.);
+
+    for _ in 0..30 {
+      lfo.process(&mut params, CvInputs::default());
+    }
+    // the phasor advances one sample from the restarted phase
+    let phase_ramp = lfo
+      .process(
+        &mut params,
+        CvInputs {
+          reset: 1.,
+          ..Default::default()
+        },
+      )
+      .phase_ramp;
+    assert!((phase_ramp - 0.1).abs() < 1e-6);
+    let phase_ramp = lfo.process(&mut params, CvInputs::default()).phase_ramp;
+    assert!((phase_ramp - 0.2).abs() < 1e-6);
+  }
+
+  #[test]
+  fn should_modulate_the_frequency_per_volt() {
+    // one volt at full depth adds one Hz in linear mode and doubles the rate in exponential mode
+    for (fm_mode, freq) in [(1., 3.), (2., 4.)] {
+      let mut lfo = Lfo::new(100.);
+      let mut params = Params::new(100.);
+      set_params(&mut params, 1., 1., 1.);
+      params.set(2., 1., 1., 1., 0.);
+      params.set_fm(fm_mode, 1.);
+
+      let first_ramp = lfo
+        .process(
+          &mut params,
+          CvInputs {
+            fm: 1.,
+            ..Default::default()
+          },
+        )
+        .phase_ramp;
+      let second_ramp = lfo
+        .process(
+          &mut params,
+          CvInputs {
+            fm: 1.,
+            ..Default::default()
+          },
+        )
+        .phase_ramp;
+      assert!((second_ramp - first_ramp - freq * 0.1).abs() < 1e-5);
+    }
+    assert_eq!(Lfo::modulate_freq(2., FmMode::Linear, -3.), 0.);
+    assert_eq!(Lfo::modulate_freq(2., FmMode::Exponential, -1.), 1.);
+  }
+
+  #[test]
+  fn should_add_the_depth_and_offset_cv_per_sample() {
+    let mut lfo = Lfo::new(100.);
+    let mut params = Params::new(100.);
+    set_params(&mut params, 5., 1., 1.);
+    params.set(1., 5., 1., 0., 0.);
+
+    // ten volts add a hundred percent to the low half of the rectangle
+    assert_eq!(
+      lfo
+        .process(
+          &mut params,
+          CvInputs {
+            depth: 10.,
+            ..Default::default()
+          }
+        )
+        .output,
+      -10.
+    );
+    assert_eq!(lfo.process(&mut params, CvInputs::default()).output, 0.);
+    assert_eq!(
+      lfo
+        .process(
+          &mut params,
+          CvInputs {
+            depth: -5.,
+            ..Default::default()
+          }
+        )
+        .output,
+      5.
+    );
+    assert_eq!(
+      lfo
+        .process(
+          &mut params,
+          CvInputs {
+            offset: 5.,
+            ..Default::default()
+          }
+        )
+        .output,
+      5.
+    );
+    // the depth is clamped to a hundred percent
+    assert_eq!(
+      lfo
+        .process(
+          &mut params,
+          CvInputs {
+            depth: 20.,
+            offset: 5.,
+            ..Default::default()
+          }
+        )
+        .output,
+      -5.
+    );
+  }
+
+  #[test]
+  fn should_add_the_pulse_width_cv() {
+    let mut lfo = Lfo::new(100.);
+    let mut params = Params::new(100.);
+    set_params(&mut params, 5., 1., 1.);
+
+    assert_eq!(lfo.process(&mut params, CvInputs::default()).output, -10.);
+    // five volts widen the pulse from fifty to a hundred percent
+    assert_eq!(
+      lfo
+        .process(
+          &mut params,
+          CvInputs {
+            pulse_width: 5.,
+            ..Default::default()
+          }
+        )
+        .output,
+      10.
+    );
+    assert_eq!(
+      lfo
+        .process(
+          &mut params,
+          CvInputs {
+            pulse_width: -10.,
+            ..Default::default()
+          }
+        )
+        .output,
+      -10.
+    );
+  }
+
+  #[test]
+  fn should_morph_between_adjacent_shapes() {
+    let mut lfo = Lfo::new(100.);
+    let mut params = Params::new(100.);
+    set_params(&mut params, 3.25, 1., 2.);
+    params.set_shape_morph(1.);
+
+    for _ in 0..24 {
+      lfo.process(&mut params, CvInputs::default());
+    }
+    // a quarter of the way from a saw up at 5 volts to a saw down at -5 volts
+    let output = lfo.process(&mut params, CvInputs::default()).output;
+    assert!((output - 2.5).abs() < 0.01);
+  }
+
+  #[test]
+  fn should_defer_a_crossfade_until_the_running_one_finishes() {
+    let mut lfo = Lfo::new(1000.);
+    let mut params = Params::new(1000.);
+    set_params(&mut params, 1., 1., 3.);
+
+    let mut previous_output = 0.;
+    for index in 0..100 {
+      match index {
+        50 => params.set(1., 5., 1., 1., 0.),
+        60 => params.set(1., 1., 1., 1., 0.),
+        _ => (),
+      }
+      let output = lfo.process(&mut params, CvInputs::default()).output;
+      assert!((output - previous_output).abs() < 1.);
+      previous_output = output;
+    }
+    // the second crossfade back to the sine finishes 20 milliseconds after the first one
+    assert!(previous_output > 0.);
+  }
+
+  #[test]
+  fn should_soft_clip_only_near_the_edges_of_the_range() {
+    assert_eq!(Lfo::apply_overflow(0.5, Overflow::SoftClip), 0.5);
+    assert_eq!(Lfo::apply_overflow(-0.8, Overflow::SoftClip), -0.8);
+    assert_eq!(Lfo::apply_overflow(1., Overflow::SoftClip), 1.);
+    assert_eq!(Lfo::apply_overflow(-1., Overflow::SoftClip), -1.);
+    assert_eq!(Lfo::apply_overflow(1.5, Overflow::SoftClip), 1.);
+    let value = Lfo::apply_overflow(0.9, Overflow::SoftClip);
+    assert!(value > 0.9 && value < 1.);
+  }
+
+  #[test]
+  fn should_fold_and_wrap_overflowing_values() {
+    assert_eq!(Lfo::apply_overflow(0.5, Overflow::Fold), 0.5);
+    assert_eq!(Lfo::apply_overflow(1.5, Overflow::Fold), 0.5);
+    assert_eq!(Lfo::apply_overflow(-1.25, Overflow::Fold), -0.75);
+    assert_eq!(Lfo::apply_overflow(3.5, Overflow::Fold), -0.5);
+    assert_eq!(Lfo::apply_overflow(1., Overflow::Wrap), 1.);
+    assert_eq!(Lfo::apply_overflow(1.5, Overflow::Wrap), -0.5);
+    assert_eq!(Lfo::apply_overflow(-1.25, Overflow::Wrap), 0.75);
+  }
+
+  #[test]
+  fn should_map_the_output_ranges_to_volts() {
+    for (output_range, volts) in [
+      (OutputRange::Bipolar10, [-10., 0., 10.]),
+      (OutputRange::Bipolar5, [-5., 0., 5.]),
+      (OutputRange::Unipolar10, [0., 5., 10.]),
+      (OutputRange::Unipolar5, [0., 2.5, 5.]),
+    ] {
+      assert_eq!(
+        [-1., 0., 1.].map(|value| Lfo::map_output_range(value, output_range)),
+        volts
+      );
+    }
+  }
+
+  #[test]
+  fn should_keep_quantized_volts_within_the_output_range() {
+    let mut params = Params::new(100.);
+    // a scale of only C sharp
+    params.set_scale(7., 1., 1.);
+    assert!((Lfo::map_volts(1., &params) - (9. + 1. / 12.)).abs() < 1e-6);
+    assert!((Lfo::map_volts(0.95, &params) - (9. + 1. / 12.)).abs() < 1e-6);
+
+    // a scale of only B
+    params.set_scale(7., 11., 1.);
+    params.set_output_range(3.);
+    assert!((Lfo::map_volts(-1., &params) - 11. / 12.).abs() < 1e-6);
+  }
+
+  #[test]
+  fn should_bend_the_output_with_a_curve() {
+    assert_eq!(Lfo::apply_curve(0.5, 0.), 0.5);
+    assert!(Lfo::apply_curve(0., 1.) < -0.9);
+    assert!(Lfo::apply_curve(0., -1.) > 0.8);
+    for curve in [-1., -0.01, 0.01, 1.] {
+      assert_eq!(Lfo::apply_curve(-1., curve), -1.);
+      assert_eq!(Lfo::apply_curve(1., curve), 1.);
+    }
+  }
+
+  #[test]
+  fn should_quantize_to_steps() {
+    assert_eq!(Lfo::quantize(0.3, 4.), 0.25);
+    assert_eq!(Lfo::quantize(0.99, 4.), 0.75);
+    assert_eq!(Lfo::quantize_levels(0.3, 3.), 0.);
+    assert_eq!(Lfo::quantize_levels(0.6, 3.), 1.);
+    assert_eq!(Lfo::quantize_levels(-0.4, 5.), -0.5);
+    assert_eq!(Lfo::quantize_levels(0.2, 2.), 1.);
+    assert_eq!(Lfo::quantize_levels(0.2, 1.), 0.);
+  }
+}
@@ -1,8 +1,9 @@
 mod smooth;
-use smooth::LinearSmooth;
-pub use smooth::Smoother;
+mod transport;
+pub use smooth::{LinearSmooth, Smoother};
+use transport::Transport;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum LfoShape {
   Sine,
   Triangle,
@@ -15,44 +16,331 @@ pub enum LfoShape {
   Noise,
 }
 
+impl LfoShape {
+  pub fn next(self) -> Self {
+    match self {
+      LfoShape::Sine => LfoShape::Triangle,
+      LfoShape::Triangle => LfoShape::SawUp,
+      LfoShape::SawUp => LfoShape::SawDown,
+      LfoShape::SawDown => LfoShape::Rectangle,
+      LfoShape::Rectangle => LfoShape::SampleAndHold,
+      LfoShape::SampleAndHold => LfoShape::Random,
+      LfoShape::Random => LfoShape::CurvedRandom,
+      LfoShape::CurvedRandom => LfoShape::Noise,
+      LfoShape::Noise => LfoShape::Sine,
+    }
+  }
+}
+
+#[derive(Clone, Copy)]
+pub enum ShapeChange {
+  Immediate,
+  CycleEnd,
+  Crossfade,
+}
+
+#[derive(Clone, Copy)]
+pub enum StepMode {
+  Levels,
+  Time,
+}
+
+#[derive(Clone, Copy)]
+pub enum Overflow {
+  Clamp,
+  Fold,
+  Wrap,
+  SoftClip,
+}
+
+#[derive(Clone, Copy)]
+pub enum OutputRange {
+  Bipolar10,
+  Bipolar5,
+  Unipolar10,
+  Unipolar5,
+}
+
+#[derive(Clone, Copy)]
+pub enum RateMode {
+  Free,
+  Tempo,
+  Transport,
+}
+
+#[derive(Clone, Copy)]
+pub enum SyncMode {
+  Hard,
+  Soft,
+}
+
+#[derive(Clone, Copy)]
+pub enum FmMode {
+  Linear,
+  Exponential,
+}
+
+#[derive(Clone, Copy)]
+pub enum SmoothedParam {
+  Freq,
+  Depth,
+  Offset,
+  Chance,
+  PhaseOffset,
+  FmDepth,
+  PulseWidth,
+  Symmetry,
+  Morph,
+  Rise,
+  Fall,
+  Delay,
+  Fade,
+  Curve,
+  GateLength,
+  Threshold,
+  Hysteresis,
+}
+
 pub struct Params {
   pub freq: LinearSmooth,
   pub depth: LinearSmooth,
+  pub offset: LinearSmooth,
+  pub chance: LinearSmooth,
+  pub phase_offset: LinearSmooth,
+  pub fm_depth: LinearSmooth,
+  pub pulse_width: LinearSmooth,
+  pub symmetry: LinearSmooth,
+  pub morph: LinearSmooth,
+  pub rise: LinearSmooth,
+  pub fall: LinearSmooth,
+  pub delay: LinearSmooth,
+  pub fade: LinearSmooth,
+  pub curve: LinearSmooth,
+  pub gate_length: LinearSmooth,
+  pub threshold: LinearSmooth,
+  pub hysteresis: LinearSmooth,
   pub shape: LfoShape,
-  pub offset: f32,
-  pub chance: f32,
+  pub shape_morph: bool,
+  pub shape_change: ShapeChange,
+  pub rate_mode: RateMode,
+  pub division: f32,
+  pub sync_mode: SyncMode,
+  pub fm_mode: FmMode,
+  pub steps: f32,
+  pub step_mode: StepMode,
+  pub overflow: Overflow,
+  pub output_range: OutputRange,
+  pub scale_mask: u16,
+  pub root: f32,
+  pub transport: Transport,
+  free_freq: f32,
+  shape_position: f32,
   is_initialized: bool,
 }
 
+/// Each feature has its own setter, so a plugin only sets the controls it exposes.
+/// The others keep the defaults of the LV2 ports.
+/// The smoothed params jump to the values that are set before the first sample is processed.
 impl Params {
   pub fn new(sample_rate: f32) -> Self {
     Self {
-      freq: LinearSmooth::new(sample_rate, 12.),
-      depth: LinearSmooth::new(sample_rate, 12.),
+      freq: Self::smooth(sample_rate, 2.),
+      depth: Self::smooth(sample_rate, 1.),
+      offset: Self::smooth(sample_rate, 0.),
+      chance: Self::smooth(sample_rate, 1.),
+      phase_offset: Self::smooth(sample_rate, 0.),
+      fm_depth: Self::smooth(sample_rate, 0.),
+      pulse_width: Self::smooth(sample_rate, 0.5),
+      symmetry: Self::smooth(sample_rate, 0.5),
+      morph: Self::smooth(sample_rate, 0.),
+      rise: Self::smooth(sample_rate, 0.),
+      fall: Self::smooth(sample_rate, 0.),
+      delay: Self::smooth(sample_rate, 0.),
+      fade: Self::smooth(sample_rate, 0.),
+      curve: Self::smooth(sample_rate, 0.),
+      gate_length: Self::smooth(sample_rate, 10.),
+      threshold: Self::smooth(sample_rate, 0.),
+      hysteresis: Self::smooth(sample_rate, 0.),
       shape: LfoShape::Sine,
-      offset: 0.,
-      chance: 1.,
+      shape_morph: false,
+      shape_change: ShapeChange::Immediate,
+      rate_mode: RateMode::Free,
+      division: 0.25,
+      sync_mode: SyncMode::Hard,
+      fm_mode: FmMode::Linear,
+      steps: 0.,
+      step_mode: StepMode::Levels,
+      overflow: Overflow::Clamp,
+      output_range: OutputRange::Bipolar10,
+      scale_mask: 0,
+      root: 0.,
+      transport: Transport::new(sample_rate),
+      free_freq: 2.,
+      shape_position: 1.,
       is_initialized: false,
     }
   }
 
   pub fn set(&mut self, freq: f32, shape: f32, chance: f32, depth: f32, offset: f32) {
-    self.shape = Self::map_shape(shape);
-    self.chance = chance;
-    self.offset = offset;
+    self.shape_position = shape;
+    self.set_shape();
+    self.free_freq = freq;
+    self.set_freq();
+    Self::set_smooth(&mut self.chance, chance, self.is_initialized);
+    Self::set_smooth(&mut self.depth, depth, self.is_initialized);
+    Self::set_smooth(&mut self.offset, offset, self.is_initialized);
+  }
 
-    if self.is_initialized {
-      self.freq.set_target(freq);
-      self.depth.set_target(depth);
+  pub fn set_rate(&mut self, rate_mode: f32, division: f32) {
+    self.rate_mode = Self::map_rate_mode(rate_mode);
+    self.division = Self::map_division(division);
+    self.set_freq();
+  }
+
+  pub fn set_phase_offset(&mut self, phase_offset: f32) {
+    Self::set_smooth(&mut self.phase_offset, phase_offset, self.is_initialized);
+  }
+
+  pub fn set_sync_mode(&mut self, sync_mode: f32) {
+    self.sync_mode = Self::map_sync_mode(sync_mode);
+  }
+
+  pub fn set_fm(&mut self, fm_mode: f32, fm_depth: f32) {
+    self.fm_mode = Self::map_fm_mode(fm_mode);
+    Self::set_smooth(&mut self.fm_depth, fm_depth, self.is_initialized);
+  }
+
+  pub fn set_pulse_width(&mut self, pulse_width: f32) {
+    Self::set_smooth(&mut self.pulse_width, pulse_width, self.is_initialized);
+  }
+
+  pub fn set_symmetry(&mut self, symmetry: f32) {
+    Self::set_smooth(&mut self.symmetry, symmetry, self.is_initialized);
+  }
+
+  pub fn set_shape_morph(&mut self, shape_morph: f32) {
+    self.shape_morph = shape_morph == 1.;
+    self.set_shape();
+  }
+
+  pub fn set_shape_change(&mut self, shape_change: f32) {
+    self.shape_change = Self::map_shape_change(shape_change);
+  }
+
+  pub fn set_slew(&mut self, rise: f32, fall: f32) {
+    Self::set_smooth(&mut self.rise, rise, self.is_initialized);
+    Self::set_smooth(&mut self.fall, fall, self.is_initialized);
+  }
+
+  pub fn set_fade_in(&mut self, delay: f32, fade: f32) {
+    Self::set_smooth(&mut self.delay, delay, self.is_initialized);
+    Self::set_smooth(&mut self.fade, fade, self.is_initialized);
+  }
+
+  pub fn set_output_range(&mut self, output_range: f32) {
+    self.output_range = Self::map_output_range(output_range);
+  }
+
+  pub fn set_overflow(&mut self, overflow: f32) {
+    self.overflow = Self::map_overflow(overflow);
+  }
+
+  pub fn set_curve(&mut self, curve: f32) {
+    Self::set_smooth(&mut self.curve, curve, self.is_initialized);
+  }
+
+  pub fn set_steps(&mut self, steps: f32, step_mode: f32) {
+    self.steps = steps;
+    self.step_mode = Self::map_step_mode(step_mode);
+  }
+
+  pub fn set_scale(&mut self, scale: f32, root: f32, custom_scale_mask: f32) {
+    self.scale_mask = Self::map_scale(scale, custom_scale_mask);
+    self.root = root;
+  }
+
+  pub fn set_gate_length(&mut self, gate_length: f32) {
+    Self::set_smooth(&mut self.gate_length, gate_length, self.is_initialized);
+  }
+
+  pub fn set_comparator(&mut self, threshold: f32, hysteresis: f32) {
+    Self::set_smooth(&mut self.threshold, threshold, self.is_initialized);
+    Self::set_smooth(&mut self.hysteresis, hysteresis, self.is_initialized);
+  }
+
+  /// Sets the time in milliseconds it takes a parameter to glide to a new value.
+  pub fn set_smoothing_time(&mut self, param: SmoothedParam, time: f32) {
+    let smooth = match param {
+      SmoothedParam::Freq => &mut self.freq,
+      SmoothedParam::Depth => &mut self.depth,
+      SmoothedParam::Offset => &mut self.offset,
+      SmoothedParam::Chance => &mut self.chance,
+      SmoothedParam::PhaseOffset => &mut self.phase_offset,
+      SmoothedParam::FmDepth => &mut self.fm_depth,
+      SmoothedParam::PulseWidth => &mut self.pulse_width,
+      SmoothedParam::Symmetry => &mut self.symmetry,
+      SmoothedParam::Morph => &mut self.morph,
+      SmoothedParam::Rise => &mut self.rise,
+      SmoothedParam::Fall => &mut self.fall,
+      SmoothedParam::Delay => &mut self.delay,
+      SmoothedParam::Fade => &mut self.fade,
+      SmoothedParam::Curve => &mut self.curve,
+      SmoothedParam::GateLength => &mut self.gate_length,
+      SmoothedParam::Threshold => &mut self.threshold,
+      SmoothedParam::Hysteresis => &mut self.hysteresis,
+    };
+    smooth.set_time(time);
+  }
+
+  /// Ends the initialization, so from now on the smoothed params glide to new values.
+  pub(crate) fn start_smoothing(&mut self) {
+    self.is_initialized = true;
+  }
+
+  /// The tempo synced rate modes derive the frequency from the note division,
+  /// so this is called by both the free frequency and the rate mode setters.
+  fn set_freq(&mut self) {
+    let freq = match self.rate_mode {
+      RateMode::Free => self.free_freq,
+      RateMode::Tempo | RateMode::Transport => self.transport.division_to_freq(self.division),
+    };
+    Self::set_smooth(&mut self.freq, freq, self.is_initialized);
+  }
+
+  /// Without shape morphing a fractional shape snaps to the nearest shape.
+  /// With shape morphing it crossfades from the shape below towards the one above.
+  fn set_shape(&mut self) {
+    let (shape, morph) = Self::map_shape(self.shape_position, self.shape_morph);
+    // the crossfade starts over from another shape, so it can't glide from the previous fraction
+    let is_initialized = self.is_initialized && shape == self.shape;
+    self.shape = shape;
+    Self::set_smooth(&mut self.morph, morph, is_initialized);
+  }
+
+  fn smooth(sample_rate: f32, value: f32) -> LinearSmooth {
+    let mut smooth = LinearSmooth::new(sample_rate, 12.);
+    smooth.reset(value);
+    smooth
+  }
+
+  fn set_smooth(smooth: &mut LinearSmooth, value: f32, is_initialized: bool) {
+    if is_initialized {
+      smooth.set_target(value);
     } else {
-      self.freq.reset(freq);
-      self.depth.reset(depth);
-      self.is_initialized = true;
+      smooth.reset(value);
     }
   }
 
-  fn map_shape(shape: f32) -> LfoShape {
-    match shape {
+  fn map_shape(shape: f32, shape_morph: bool) -> (LfoShape, f32) {
+    let shape = shape.clamp(1., 9.);
+    let lower_shape = if shape_morph {
+      shape.floor()
+    } else {
+      shape.round()
+    };
+    let morph = if shape_morph { shape - lower_shape } else { 0. };
+
+    let shape = match lower_shape {
       1. => LfoShape::Sine,
       2. => LfoShape::Triangle,
       3. => LfoShape::SawUp,
@@ -63,6 +351,167 @@ impl Params {
       8. => LfoShape::CurvedRandom,
       9. => LfoShape::Noise,
       _ => panic!("Shape is invalid."),
+    };
+    (shape, morph)
+  }
+
+  fn map_shape_change(shape_change: f32) -> ShapeChange {
+    match shape_change {
+      1. => ShapeChange::Immediate,
+      2. => ShapeChange::CycleEnd,
+      3. => ShapeChange::Crossfade,
+      _ => panic!("Shape change is invalid."),
+    }
+  }
+
+  /// Maps the scale to a mask where bit n enables the note n semitones above the root.
+  fn map_scale(scale: f32, custom_scale_mask: f32) -> u16 {
+    match scale {
+      1. => 0,
+      2. => 0b1111_1111_1111,
+      3. => 0b1010_1011_0101,
+      4. => 0b0101_1010_1101,
+      5. => 0b0010_1001_0101,
+      6. => 0b0100_1010_1001,
+      7. => custom_scale_mask as u16 & 0b1111_1111_1111,
+      _ => panic!("Scale is invalid."),
+    }
+  }
+
+  fn map_rate_mode(rate_mode: f32) -> RateMode {
+    match rate_mode {
+      1. => RateMode::Free,
+      2. => RateMode::Tempo,
+      3. => RateMode::Transport,
+      _ => panic!("Rate mode is invalid."),
+    }
+  }
+
+  fn map_sync_mode(sync_mode: f32) -> SyncMode {
+    match sync_mode {
+      1. => SyncMode::Hard,
+      2. => SyncMode::Soft,
+      _ => panic!("Sync mode is invalid."),
+    }
+  }
+
+  fn map_fm_mode(fm_mode: f32) -> FmMode {
+    match fm_mode {
+      1. => FmMode::Linear,
+      2. => FmMode::Exponential,
+      _ => panic!("FM mode is invalid."),
+    }
+  }
+
+  fn map_step_mode(step_mode: f32) -> StepMode {
+    match step_mode {
+      1. => StepMode::Levels,
+      2. => StepMode::Time,
+      _ => panic!("Step mode is invalid."),
+    }
+  }
+
+  fn map_overflow(overflow: f32) -> Overflow {
+    match overflow {
+      1. => Overflow::Clamp,
+      2. => Overflow::Fold,
+      3. => Overflow::Wrap,
+      4. => Overflow::SoftClip,
+      _ => panic!("Overflow is invalid."),
     }
   }
+
+  fn map_output_range(output_range: f32) -> OutputRange {
+    match output_range {
+      1. => OutputRange::Bipolar10,
+      2. => OutputRange::Bipolar5,
+      3. => OutputRange::Unipolar10,
+      4. => OutputRange::Unipolar5,
+      _ => panic!("Output range is invalid."),
+    }
+  }
+
+  /// Maps the division port value to a note length in whole notes.
+  /// Each note value from 1/1 to 1/64 comes in a dotted, straight and triplet variant.
+  fn map_division(division: f32) -> f32 {
+    let index = division as i32 - 1;
+    let note_length = 0.5_f32.powi(index / 3);
+    match index % 3 {
+      0 => note_length * 1.5,
+      1 => note_length,
+      2 => note_length * 2. / 3.,
+      _ => panic!("Division is invalid."),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{LfoShape, Params, SmoothedParam, Smoother};
+
+  #[test]
+  fn should_convert_divisions_to_tempo_synced_frequencies() {
+    let mut params = Params::new(44100.);
+    params.transport.set_bpm(120.);
+    params.set(1., 1., 1., 1., 0.);
+    params.start_smoothing();
+
+    params.set_rate(2., 8.);
+    assert_eq!(params.freq.get_target(), 2.);
+    params.set_rate(2., 10.);
+    assert_eq!(params.freq.get_target(), 4. / 1.5);
+    params.set_rate(2., 12.);
+    assert_eq!(params.freq.get_target(), 6.);
+    params.set_rate(2., 2.);
+    assert_eq!(params.freq.get_target(), 0.5);
+    params.set_rate(1., 2.);
+    assert_eq!(params.freq.get_target(), 1.);
+
+    params.transport.set_beat_unit(8.);
+    params.set_rate(2., 8.);
+    assert_eq!(params.freq.get_target(), 1.);
+  }
+
+  #[test]
+  fn should_smooth_each_parameter_in_its_own_time() {
+    let mut params = Params::new(1000.);
+    params.set(1., 1., 1., 0., 0.);
+    params.start_smoothing();
+    params.set_smoothing_time(SmoothedParam::Freq, 10.);
+    params.set_smoothing_time(SmoothedParam::Depth, 20.);
+    params.set_smoothing_time(SmoothedParam::Offset, 0.);
+    params.set_smoothing_time(SmoothedParam::Chance, 5.);
+    params.set(2., 1., 0., 1., 1.);
+
+    assert_eq!(params.offset.next(), 1.);
+    for _ in 0..4 {
+      params.chance.next();
+    }
+    assert!(params.chance.next().abs() < 1e-6);
+    for _ in 0..9 {
+      params.freq.next();
+      params.depth.next();
+    }
+    assert!((params.freq.next() - 2.).abs() < 1e-6);
+    assert!((params.depth.next() - 0.5).abs() < 1e-6);
+  }
+
+  #[test]
+  fn should_map_fractional_shapes() {
+    assert!(Params::map_shape(3., false) == (LfoShape::SawUp, 0.));
+    assert!(Params::map_shape(3.4, false) == (LfoShape::SawUp, 0.));
+    assert!(Params::map_shape(3.6, false) == (LfoShape::SawDown, 0.));
+    assert!(Params::map_shape(3.25, true) == (LfoShape::SawUp, 0.25));
+    assert!(Params::map_shape(3.75, true) == (LfoShape::SawUp, 0.75));
+    assert!(Params::map_shape(9., true) == (LfoShape::Noise, 0.));
+  }
+
+  #[test]
+  fn should_map_divisions_to_note_lengths() {
+    assert_eq!(Params::map_division(2.), 1.);
+    assert_eq!(Params::map_division(7.), 0.375);
+    assert_eq!(Params::map_division(8.), 0.25);
+    assert_eq!(Params::map_division(9.), 0.25 * 2. / 3.);
+    assert_eq!(Params::map_division(20.), 1. / 64.);
+  }
 }
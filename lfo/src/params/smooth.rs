@@ -1,4 +1,4 @@
-use std::f32::consts::TAU;
+use {crate::shared::float_ext::FloatExt, std::f32::consts::TAU};
 
 pub trait Smoother {
   fn reset(&mut self, target: f32);
@@ -11,6 +11,7 @@ pub trait Smoother {
 }
 
 pub struct LinearSmooth {
+  sample_rate: f32,
   current: f32,
   target: f32,
   factor: f32,
@@ -22,6 +23,7 @@ pub struct LinearSmooth {
 impl LinearSmooth {
   pub fn new(sample_rate: f32, frequency: f32) -> Self {
     Self {
+      sample_rate,
       current: 0.,
       target: 0.,
       factor: sample_rate.recip() * frequency,
@@ -30,11 +32,19 @@ impl LinearSmooth {
       step_counter: 0,
     }
   }
+
+  /// Sets the time in milliseconds it takes to glide to a new target.
+  pub fn set_time(&mut self, time: f32) {
+    let steps = time.mstosamps(self.sample_rate).max(1.);
+    self.factor = steps.recip();
+    self.steps = steps as usize;
+  }
 }
 
 impl Smoother for LinearSmooth {
   fn reset(&mut self, target: f32) {
     self.current = target;
+    self.target = target;
     self.step_counter = 0;
   }
 
@@ -178,4 +188,25 @@ mod tests {
     assert_eq!(linear_smooth.next(), 2.9802322e-8);
     assert_eq!(linear_smooth.next(), 0.0);
   }
+
+  #[test]
+  fn should_smooth_linearly_with_a_custom_time() {
+    let mut linear_smooth = LinearSmooth::new(5., 1.);
+    linear_smooth.set_time(400.);
+    linear_smooth.set_target(1.);
+    assert_eq!(linear_smooth.next(), 0.5);
+    assert_eq!(linear_smooth.next(), 1.0);
+    assert_eq!(linear_smooth.next(), 1.0);
+  }
+
+  #[test]
+  fn should_hold_the_reset_value() {
+    let mut linear_smooth = LinearSmooth::new(5., 1.);
+    linear_smooth.set_target(1.);
+    linear_smooth.next();
+    linear_smooth.reset(0.5);
+    assert_eq!(linear_smooth.get_target(), 0.5);
+    assert_eq!(linear_smooth.next(), 0.5);
+    assert_eq!(linear_smooth.next(), 0.5);
+  }
 }
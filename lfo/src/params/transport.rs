@@ -0,0 +1,139 @@
+pub struct Transport {
+  sample_period: f32,
+  bpm: f32,
+  beat_unit: f32,
+  beats_per_bar: f32,
+  bar: f64,
+  bar_beat: f64,
+  speed: f32,
+}
+
+impl Transport {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      sample_period: sample_rate.recip(),
+      bpm: 120.,
+      beat_unit: 4.,
+      beats_per_bar: 4.,
+      bar: 0.,
+      bar_beat: 0.,
+      speed: 0.,
+    }
+  }
+
+  pub fn set_bpm(&mut self, bpm: f32) {
+    self.bpm = bpm;
+  }
+
+  pub fn set_beat_unit(&mut self, beat_unit: f32) {
+    self.beat_unit = beat_unit;
+  }
+
+  pub fn set_beats_per_bar(&mut self, beats_per_bar: f32) {
+    self.beats_per_bar = beats_per_bar;
+  }
+
+  pub fn set_bar(&mut self, bar: f64) {
+    self.bar = bar;
+  }
+
+  pub fn set_bar_beat(&mut self, bar_beat: f64) {
+    self.bar_beat = bar_beat;
+  }
+
+  pub fn set_speed(&mut self, speed: f32) {
+    self.speed = speed;
+  }
+
+  pub fn is_rolling(&self) -> bool {
+    self.speed != 0.
+  }
+
+  /// Converts a note division, expressed in whole notes, to a frequency in Hz.
+  pub fn division_to_freq(&self, division: f32) -> f32 {
+    self.bpm / (60. * division * self.beat_unit)
+  }
+
+  /// Returns the phase of a note division, expressed in whole notes, at the current song position.
+  /// The phase is computed from the start of the song, so it lands on the same spot for every playback.
+  pub fn get_phase(&self, division: f32) -> f32 {
+    self.get_cycles(division).rem_euclid(1.) as f32
+  }
+
+  /// Returns the number of note divisions since the start of the song.
+  pub fn get_cycles(&self, division: f32) -> f64 {
+    let beats = self.bar * self.beats_per_bar as f64 + self.bar_beat;
+    let division_in_beats = (division * self.beat_unit) as f64;
+    beats / division_in_beats
+  }
+
+  /// Advances the song position by one sample while the host transport is rolling.
+  pub fn process(&mut self) {
+    if !self.is_rolling() {
+      return;
+    }
+
+    self.bar_beat += (self.bpm * self.speed * self.sample_period / 60.) as f64;
+    let beats_per_bar = self.beats_per_bar as f64;
+    if self.bar_beat >= beats_per_bar {
+      self.bar_beat -= beats_per_bar;
+      self.bar += 1.;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Transport;
+
+  #[test]
+  fn should_convert_divisions_to_frequencies() {
+    let mut transport = Transport::new(44100.);
+    transport.set_bpm(120.);
+    assert_eq!(transport.division_to_freq(0.25), 2.);
+    assert_eq!(transport.division_to_freq(0.375), 4. / 3.);
+    assert_eq!(transport.division_to_freq(1.), 0.5);
+
+    transport.set_beat_unit(8.);
+    assert_eq!(transport.division_to_freq(0.25), 1.);
+  }
+
+  #[test]
+  fn should_derive_phase_from_song_position() {
+    let mut transport = Transport::new(8.);
+    transport.set_bpm(120.);
+    transport.set_speed(1.);
+    transport.set_bar(2.);
+    transport.set_bar_beat(1.);
+
+    assert_eq!(transport.get_phase(0.25), 0.);
+    assert_eq!(transport.get_phase(0.5), 0.5);
+    assert_eq!(transport.get_phase(1.), 0.25);
+    assert_eq!(transport.get_cycles(0.25), 9.);
+
+    // 120 bpm at a sample rate of 8 Hz advances a quarter beat per sample
+    transport.process();
+    assert_eq!(transport.get_phase(0.25), 0.25);
+    transport.process();
+    transport.process();
+    transport.process();
+    assert_eq!(transport.get_phase(1.), 0.5);
+
+    transport.set_speed(0.);
+    transport.process();
+    assert_eq!(transport.get_phase(1.), 0.5);
+  }
+
+  #[test]
+  fn should_wrap_bar_beat_into_the_next_bar() {
+    let mut transport = Transport::new(2.);
+    transport.set_bpm(120.);
+    transport.set_speed(1.);
+    transport.set_bar_beat(3.);
+
+    transport.process();
+    assert_eq!(transport.bar, 1.);
+    assert_eq!(transport.bar_beat, 0.);
+    assert_eq!(transport.get_phase(1.), 0.);
+  }
+}
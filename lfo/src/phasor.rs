@@ -16,9 +16,17 @@ impl Phasor {
     self.x
   }
 
+  pub fn get_phase(&self) -> f32 {
+    self.x
+  }
+
+  pub fn reset(&mut self, phase: f32) {
+    self.x = phase;
+  }
+
   fn wrap(&self, input: f32) -> f32 {
     if input >= 1. {
-      input - 1.
+      input.fract()
     } else {
       input
     }
@@ -0,0 +1,49 @@
+/// Snaps a 1V/oct voltage to the nearest note in the scale mask that lies within the voltage range.
+/// Bit n of the mask enables the note n semitones above the root.
+pub fn quantize(volts: f32, scale_mask: u16, root: f32, min_volts: f32, max_volts: f32) -> f32 {
+  let semitones = volts * 12. - root;
+  let center = semitones.round() as i32;
+
+  let mut nearest = None;
+  let mut nearest_distance = f32::MAX;
+  for note in center - 12..=center + 12 {
+    let note_volts = (note as f32 + root) / 12.;
+    if scale_mask & (1 << note.rem_euclid(12)) == 0
+      || note_volts < min_volts
+      || note_volts > max_volts
+    {
+      continue;
+    }
+    let distance = (note as f32 - semitones).abs();
+    if distance < nearest_distance {
+      nearest = Some(note);
+      nearest_distance = distance;
+    }
+  }
+
+  match nearest {
+    Some(note) => (note as f32 + root) / 12.,
+    None => volts,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::quantize;
+
+  const MAJOR: u16 = 0b1010_1011_0101;
+
+  #[test]
+  fn should_snap_to_the_nearest_note_in_the_scale() {
+    assert_eq!(quantize(0.05, 0xFFF, 0., -10., 10.), 1. / 12.);
+    assert_eq!(quantize(3.2 / 12., MAJOR, 0., -10., 10.), 4. / 12.);
+    assert_eq!(quantize(-1. / 12., MAJOR, 0., -10., 10.), -1. / 12.);
+    assert_eq!(quantize(-1.5 / 12., MAJOR, 0., -10., 10.), -1. / 12.);
+    assert_eq!(quantize(1. / 12., MAJOR, 2., -10., 10.), 1. / 12.);
+    assert_eq!(quantize(2.5 / 12., MAJOR, 2., -10., 10.), 2. / 12.);
+    assert_eq!(quantize(0.3, 0, 0., -10., 10.), 0.3);
+    // only notes within the range
+    assert_eq!(quantize(10., 0b10, 0., -10., 10.), 9. + 1. / 12.);
+    assert_eq!(quantize(0., 0b1000_0000_0000, 0., 0., 10.), 11. / 12.);
+  }
+}
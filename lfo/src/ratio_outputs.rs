@@ -0,0 +1,155 @@
+use crate::{delta::Delta, shaper::Shaper, slew::Slew, Lfo, Params};
+
+pub const RATIO_OUTPUTS: usize = 4;
+
+pub struct RatioOutputs {
+  ratios: [f32; RATIO_OUTPUTS],
+  cycle: u32,
+  deltas: [Delta; RATIO_OUTPUTS],
+  shapers: [Shaper; RATIO_OUTPUTS],
+  slews: [Slew; RATIO_OUTPUTS],
+}
+
+impl RatioOutputs {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      ratios: [0.25, 0.5, 2., 3.],
+      // the forced trigger on initial load starts counting at zero
+      cycle: u32::MAX,
+      deltas: std::array::from_fn(|_| Delta::new()),
+      shapers: [Shaper::new(); RATIO_OUTPUTS],
+      slews: std::array::from_fn(|_| Slew::new(sample_rate)),
+    }
+  }
+
+  pub fn set(&mut self, ratio_1: f32, ratio_2: f32, ratio_3: f32, ratio_4: f32) {
+    self.ratios = [ratio_1, ratio_2, ratio_3, ratio_4].map(Self::map_ratio);
+  }
+
+  /// Returns the clock divider and multiplier outputs. Call this after processing the lfo.
+  pub fn process(&mut self, lfo: &Lfo, params: &Params) -> [f32; RATIO_OUTPUTS] {
+    let frame = lfo.get_frame();
+    if frame.trigger {
+      self.cycle = if frame.is_reset {
+        0
+      } else {
+        self.cycle.wrapping_add(1)
+      };
+    }
+
+    // the outputs derive their phase from the same cycle, so they stay in phase with the lfo
+    std::array::from_fn(|index| {
+      let ratio = self.ratios[index];
+      let phase = match frame.song_cycles {
+        // follow the song position, so dividers land on the same cycle for every playback
+        Some(cycles) => (cycles * ratio as f64).rem_euclid(1.) as f32,
+        None => Self::get_ratio_phase(frame.phase, self.cycle, ratio),
+      };
+      if self.deltas[index].process(phase) < -0.5 || frame.is_reset {
+        self.shapers[index].trigger(frame.chance);
+      }
+      let value = lfo.get_shape_value(&self.shapers[index], phase, params);
+      lfo.render(value, &mut self.slews[index], params)
+    })
+  }
+
+  /// Multiplies the phase for ratios above one.
+  /// Below one the phase is divided and counts through the cycles of the lfo.
+  fn get_ratio_phase(phase: f32, cycle: u32, ratio: f32) -> f32 {
+    if ratio >= 1. {
+      (phase * ratio).fract()
+    } else {
+      let cycles = ratio.recip().round() as u32;
+      ((cycle % cycles) as f32 + phase) * ratio
+    }
+  }
+
+  /// Maps the ratio port value to a multiplier of the rate.
+  fn map_ratio(ratio: f32) -> f32 {
+    match ratio {
+      1. => 0.125,
+      2. => 0.25,
+      3. => 1. / 3.,
+      4. => 0.5,
+      5. => 2.,
+      6. => 3.,
+      7. => 4.,
+      8. => 8.,
+      _ => panic!("Ratio is invalid."),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::RatioOutputs;
+  use crate::{CvInputs, Lfo, Params};
+
+  #[test]
+  fn should_divide_and_multiply_the_phase() {
+    assert_eq!(RatioOutputs::get_ratio_phase(0.25, 0, 2.), 0.5);
+    assert_eq!(RatioOutputs::get_ratio_phase(0.75, 7, 2.), 0.5);
+    assert_eq!(RatioOutputs::get_ratio_phase(0.5, 0, 0.5), 0.25);
+    assert_eq!(RatioOutputs::get_ratio_phase(0.5, 3, 0.5), 0.75);
+    assert_eq!(RatioOutputs::get_ratio_phase(0.5, 6, 0.25), 0.625);
+  }
+
+  #[test]
+  fn should_keep_ratio_outputs_in_phase() {
+    let mut lfo = Lfo::new(100.);
+    let mut ratio_outputs = RatioOutputs::new(100.);
+    let mut params = Params::new(100.);
+    params.set(1., 3., 1., 1., 0.);
+    ratio_outputs.set(2., 4., 5., 6.);
+
+    for _ in 0..100 {
+      lfo.process(&mut params, CvInputs::default());
+      ratio_outputs.process(&lfo, &params);
+    }
+    let output = lfo.process(&mut params, CvInputs::default()).output;
+    let [divided_by_4, divided_by_2, multiplied_by_2, multiplied_by_3] =
+      ratio_outputs.process(&lfo, &params);
+    // a saw up at a phase of 0.01 in the second cycle
+    assert!((output - 0.2).abs() < 0.01);
+    assert!((divided_by_4 - 5.05).abs() < 0.01);
+    assert!((divided_by_2 + 9.9).abs() < 0.01);
+    assert!((multiplied_by_2 - 0.4).abs() < 0.01);
+    assert!((multiplied_by_3 - 0.6).abs() < 0.01);
+  }
+
+  #[test]
+  fn should_divide_from_the_song_position() {
+    let mut lfo = Lfo::new(100.);
+    let mut ratio_outputs = RatioOutputs::new(100.);
+    let mut params = Params::new(100.);
+    params.set(1., 3., 1., 1., 0.);
+    params.set_rate(3., 8.);
+    params.transport.set_speed(1.);
+    ratio_outputs.set(2., 4., 5., 6.);
+
+    // at 120 bpm a quarter note takes 50 samples, so 150 samples later the song is at the fourth beat
+    for _ in 0..150 {
+      lfo.process(&mut params, CvInputs::default());
+      ratio_outputs.process(&lfo, &params);
+    }
+    lfo.process(&mut params, CvInputs::default());
+    let outputs = ratio_outputs.process(&lfo, &params);
+
+    let mut located_lfo = Lfo::new(100.);
+    let mut located_ratio_outputs = RatioOutputs::new(100.);
+    let mut located_params = Params::new(100.);
+    located_params.set(1., 3., 1., 1., 0.);
+    located_params.set_rate(3., 8.);
+    located_params.transport.set_speed(1.);
+    located_params.transport.set_bar_beat(3.);
+    located_ratio_outputs.set(2., 4., 5., 6.);
+    located_lfo.process(&mut located_params, CvInputs::default());
+    let located_outputs = located_ratio_outputs.process(&located_lfo, &located_params);
+
+    // a saw up at three quarters of the divided by four cycle
+    assert!((outputs[0] + 5.).abs() < 0.01);
+    for (output, located_output) in outputs.into_iter().zip(located_outputs) {
+      assert!((output - located_output).abs() < 0.01);
+    }
+  }
+}
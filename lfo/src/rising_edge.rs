@@ -0,0 +1,20 @@
+use crate::delta::Delta;
+
+const THRESHOLD: f32 = 0.5;
+
+pub struct RisingEdge {
+  delta: Delta,
+}
+
+impl RisingEdge {
+  pub fn new() -> Self {
+    Self {
+      delta: Delta::new(),
+    }
+  }
+
+  pub fn process(&mut self, input: f32) -> bool {
+    let is_high = if input > THRESHOLD { 1. } else { 0. };
+    self.delta.process(is_high) > 0.
+  }
+}
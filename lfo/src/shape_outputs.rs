@@ -0,0 +1,82 @@
+use crate::{params::LfoShape, slew::Slew, Lfo, Params};
+
+pub const SHAPE_OUTPUTS: usize = 4;
+// the shapes of the simultaneous outputs, like the outputs of a hardware LFO
+const SHAPES: [LfoShape; SHAPE_OUTPUTS] = [
+  LfoShape::Sine,
+  LfoShape::Triangle,
+  LfoShape::SawUp,
+  LfoShape::Rectangle,
+];
+
+pub struct ShapeOutputs {
+  slews: [Slew; SHAPE_OUTPUTS],
+}
+
+impl ShapeOutputs {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      slews: std::array::from_fn(|_| Slew::new(sample_rate)),
+    }
+  }
+
+  /// Returns a sine, triangle, saw up and rectangle output driven by the same phase as the lfo.
+  /// They are stepped, slewed, curved and quantized like the output. Call this after processing the lfo.
+  pub fn process(&mut self, lfo: &Lfo, params: &Params) -> [f32; SHAPE_OUTPUTS] {
+    let frame = lfo.get_frame();
+    let phase = Lfo::step_phase(frame.phase, params);
+    let shaper = lfo.get_shaper();
+    std::array::from_fn(|index| {
+      let value = shaper.process(
+        SHAPES[index],
+        phase,
+        frame.chance,
+        frame.pulse_width,
+        frame.symmetry,
+        0.,
+      );
+      lfo.render(value, &mut self.slews[index], params)
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::ShapeOutputs;
+  use crate::{CvInputs, Lfo, Params};
+
+  #[test]
+  fn should_output_all_shapes_from_the_same_phase() {
+    let mut lfo = Lfo::new(100.);
+    let mut shape_outputs = ShapeOutputs::new(100.);
+    let mut params = Params::new(100.);
+    params.set(1., 1., 1., 1., 0.);
+
+    for _ in 0..24 {
+      lfo.process(&mut params, CvInputs::default());
+    }
+    let output = lfo.process(&mut params, CvInputs::default()).output;
+    let [sine, triangle, saw, rectangle] = shape_outputs.process(&lfo, &params);
+    assert_eq!(sine, output);
+    assert!((sine - 10.).abs() < 0.01);
+    assert!((triangle - 10.).abs() < 0.01);
+    assert!((saw - 5.).abs() < 0.01);
+    assert_eq!(rectangle, -10.);
+  }
+
+  #[test]
+  fn should_step_the_shapes_like_the_output() {
+    let mut lfo = Lfo::new(100.);
+    let mut shape_outputs = ShapeOutputs::new(100.);
+    let mut params = Params::new(100.);
+    params.set(1., 1., 1., 1., 0.);
+    params.set_steps(3., 1.);
+
+    for _ in 0..25 {
+      lfo.process(&mut params, CvInputs::default());
+    }
+    let [_, _, saw, _] = shape_outputs.process(&lfo, &params);
+    // a saw up halfway up rounds to the highest of three levels
+    assert_eq!(saw, 10.);
+  }
+}
@@ -0,0 +1,225 @@
+use {
+  crate::{params::LfoShape, shared::float_ext::FloatExt},
+  std::f32::consts::{PI, TAU},
+};
+
+#[derive(Clone, Copy)]
+pub struct Shaper {
+  is_enabled: bool,
+  origin: f32,
+  target: f32,
+  previous_is_enabled: bool,
+  previous_origin: f32,
+}
+
+impl Shaper {
+  pub fn new() -> Self {
+    Self {
+      is_enabled: true,
+      origin: 0.5,
+      target: 0.5,
+      previous_is_enabled: true,
+      previous_origin: 0.5,
+    }
+  }
+
+  /// Starts a new cycle by rolling the chance decision and the next random target.
+  pub fn trigger(&mut self, chance: f32) {
+    self.previous_is_enabled = self.is_enabled;
+    self.previous_origin = self.origin;
+    self.is_enabled = fastrand::f32() <= chance;
+    self.origin = self.target;
+    self.target = if self.is_enabled {
+      fastrand::f32() * 2. - 1.
+    } else {
+      0.
+    };
+  }
+
+  /// Returns the shaper as it was during the previous cycle,
+  /// so outputs that lag behind replay the same random decisions.
+  pub fn previous_cycle(&self) -> Self {
+    Self {
+      is_enabled: self.previous_is_enabled,
+      origin: self.previous_origin,
+      target: self.origin,
+      ..*self
+    }
+  }
+
+  /// Morph crossfades from the given shape towards the next one.
+  pub fn process(
+    &self,
+    shape: LfoShape,
+    phase: f32,
+    chance: f32,
+    pulse_width: f32,
+    symmetry: f32,
+    morph: f32,
+  ) -> f32 {
+    let value = self.get_shape_value(shape, phase, chance, pulse_width, symmetry);
+    if morph > 0. {
+      value.mix(
+        self.get_shape_value(shape.next(), phase, chance, pulse_width, symmetry),
+        morph,
+      )
+    } else {
+      value
+    }
+  }
+
+  fn get_shape_value(
+    &self,
+    shape: LfoShape,
+    phase: f32,
+    chance: f32,
+    pulse_width: f32,
+    symmetry: f32,
+  ) -> f32 {
+    match shape {
+      LfoShape::Sine => {
+        if !self.is_enabled {
+          return 0.;
+        }
+
+        (phase * TAU).fast_sin()
+      }
+      LfoShape::Triangle => {
+        if !self.is_enabled {
+          return 0.;
+        }
+
+        Self::skewed_triangle(phase, symmetry)
+      }
+      LfoShape::SawUp => {
+        if !self.is_enabled {
+          return 0.;
+        }
+
+        Self::wrap(phase + 0.5) * 2. - 1.
+      }
+      LfoShape::SawDown => {
+        if !self.is_enabled {
+          return 0.;
+        }
+
+        Self::wrap(1.5 - phase) * 2. - 1.
+      }
+      LfoShape::Rectangle => {
+        if !self.is_enabled {
+          return 0.;
+        }
+
+        if phase > 1. - pulse_width {
+          1.
+        } else {
+          -1.
+        }
+      }
+      LfoShape::SampleAndHold => self.target,
+      LfoShape::Random => self.linear_interp(phase),
+      LfoShape::CurvedRandom => self.cosine_interp(phase),
+      LfoShape::Noise => {
+        if fastrand::f32() <= chance {
+          fastrand::f32() * 2. - 1.
+        } else {
+          0.
+        }
+      }
+    }
+  }
+
+  /// Skews the triangle from a saw down at zero symmetry, to a triangle at half and a saw up at one.
+  fn skewed_triangle(phase: f32, symmetry: f32) -> f32 {
+    // shifts the ramps in phase with the saw up at full symmetry and the saw down at zero symmetry
+    let phase = Self::wrap(phase + (symmetry - 0.5).abs() * 0.5 + 0.25);
+    if phase < symmetry {
+      phase / symmetry * 2. - 1.
+    } else {
+      1. - (phase - symmetry) / (1. - symmetry) * 2.
+    }
+  }
+
+  fn linear_interp(&self, mix: f32) -> f32 {
+    self.origin + (self.target - self.origin) * mix
+  }
+
+  fn cosine_interp(&self, mix: f32) -> f32 {
+    let cosine_mix = (1. - (mix * PI).fast_cos()) * 0.5;
+    self.origin + (self.target - self.origin) * cosine_mix
+  }
+
+  fn wrap(x: f32) -> f32 {
+    if x >= 1. {
+      x - 1.
+    } else {
+      x
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Shaper;
+  use crate::params::LfoShape;
+
+  #[test]
+  fn should_skew_the_triangle_into_saws() {
+    for phase in [0., 0.2, 0.45, 0.7, 0.95] {
+      assert_eq!(
+        Shaper::skewed_triangle(phase, 1.),
+        Shaper::wrap(phase + 0.5) * 2. - 1.
+      );
+      assert!(
+        (Shaper::skewed_triangle(phase, 0.) - (Shaper::wrap(1.5 - phase) * 2. - 1.)).abs() < 1e-6
+      );
+    }
+    assert_eq!(Shaper::skewed_triangle(0., 0.5), 0.);
+    assert_eq!(Shaper::skewed_triangle(0.25, 0.5), 1.);
+    assert_eq!(Shaper::skewed_triangle(0.75, 0.5), -1.);
+    assert_eq!(Shaper::skewed_triangle(0.875, 0.25), 1.);
+  }
+
+  #[test]
+  fn should_raise_the_rectangle_for_the_pulse_width() {
+    let shaper = Shaper::new();
+    // the pulse sits at the end of the cycle
+    assert_eq!(
+      shaper.process(LfoShape::Rectangle, 0.7, 1., 0.25, 0.5, 0.),
+      -1.
+    );
+    assert_eq!(
+      shaper.process(LfoShape::Rectangle, 0.75, 1., 0.25, 0.5, 0.),
+      -1.
+    );
+    assert_eq!(
+      shaper.process(LfoShape::Rectangle, 0.76, 1., 0.25, 0.5, 0.),
+      1.
+    );
+    assert_eq!(
+      shaper.process(LfoShape::Rectangle, 0.99, 1., 0., 0.5, 0.),
+      -1.
+    );
+    assert_eq!(
+      shaper.process(LfoShape::Rectangle, 0.01, 1., 1., 0.5, 0.),
+      1.
+    );
+  }
+
+  #[test]
+  fn should_replay_the_previous_cycle() {
+    let mut shaper = Shaper::new();
+    shaper.trigger(1.);
+    let first_cycle = shaper;
+    shaper.trigger(0.);
+
+    let previous_cycle = shaper.previous_cycle();
+    for shape in [LfoShape::Sine, LfoShape::SampleAndHold, LfoShape::Random] {
+      assert_eq!(
+        previous_cycle.process(shape, 0.3, 1., 0.5, 0.5, 0.),
+        first_cycle.process(shape, 0.3, 1., 0.5, 0.5, 0.)
+      );
+    }
+    assert_eq!(shaper.process(LfoShape::Sine, 0.3, 1., 0.5, 0.5, 0.), 0.);
+  }
+}
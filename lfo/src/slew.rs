@@ -0,0 +1,45 @@
+use crate::shared::float_ext::FloatExt;
+
+pub struct Slew {
+  sample_rate: f32,
+  z: f32,
+}
+
+impl Slew {
+  pub fn new(sample_rate: f32) -> Self {
+    Self { sample_rate, z: 0. }
+  }
+
+  /// Limits how fast the input can change. The rise and fall times in milliseconds
+  /// are the times it takes to travel the full range from -1 to 1.
+  pub fn process(&mut self, input: f32, rise: f32, fall: f32) -> f32 {
+    let difference = input - self.z;
+    let time = if difference > 0. { rise } else { fall };
+    let samples = time.mstosamps(self.sample_rate);
+    if samples <= 1. {
+      self.z = input;
+      return input;
+    }
+
+    let max_step = 2. / samples;
+    self.z += difference.clamp(-max_step, max_step);
+    self.z
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Slew;
+
+  #[test]
+  fn should_limit_rise_and_fall_independently() {
+    let mut slew = Slew::new(1000.);
+    assert_eq!(slew.process(1., 4., 0.), 0.5);
+    assert_eq!(slew.process(1., 4., 0.), 1.);
+    assert_eq!(slew.process(1., 4., 0.), 1.);
+    assert_eq!(slew.process(-1., 4., 0.), -1.);
+    assert_eq!(slew.process(-1., 0., 8.), -1.);
+    assert_eq!(slew.process(1., 0., 8.), 1.);
+    assert_eq!(slew.process(0., 0., 8.), 0.75);
+  }
+}
@@ -0,0 +1,93 @@
+use crate::{
+  params::{LinearSmooth, Smoother},
+  slew::Slew,
+  Lfo, Params,
+};
+
+pub const MAX_SPREAD_OUTPUTS: usize = 8;
+
+pub struct SpreadOutputs {
+  spread: LinearSmooth,
+  outputs: usize,
+  slews: [Slew; MAX_SPREAD_OUTPUTS],
+  is_initialized: bool,
+}
+
+impl SpreadOutputs {
+  pub fn new(sample_rate: f32) -> Self {
+    let mut spread = LinearSmooth::new(sample_rate, 12.);
+    spread.reset(1.);
+
+    Self {
+      spread,
+      outputs: 1,
+      slews: std::array::from_fn(|_| Slew::new(sample_rate)),
+      is_initialized: false,
+    }
+  }
+
+  pub fn set(&mut self, spread: f32, outputs: f32) {
+    if self.is_initialized {
+      self.spread.set_target(spread);
+    } else {
+      self.spread.reset(spread);
+    }
+    self.outputs = (outputs as usize).clamp(1, MAX_SPREAD_OUTPUTS);
+  }
+
+  /// Sets the time in milliseconds it takes the spread to glide to a new value.
+  pub fn set_smoothing_time(&mut self, time: f32) {
+    self.spread.set_time(time);
+  }
+
+  /// Spreads the phase of the outputs evenly over the spread amount of a cycle.
+  /// The first output equals the output of the lfo, the outputs beyond the output count are silent.
+  /// Call this after processing the lfo.
+  pub fn process(&mut self, lfo: &Lfo, params: &Params) -> [f32; MAX_SPREAD_OUTPUTS] {
+    self.is_initialized = true;
+    let spread = self.spread.next();
+    let frame = lfo.get_frame();
+    let mut outputs = [0.; MAX_SPREAD_OUTPUTS];
+
+    outputs[0] = frame.output;
+    // the outputs lag behind the first one, so a lag past the start of the cycle
+    // replays the random decisions of the previous cycle
+    for index in 1..self.outputs {
+      let lagging_phase = frame.phase - index as f32 * spread / self.outputs as f32;
+      let (shaper, lagging_phase) = if lagging_phase < 0. {
+        (lfo.get_shaper().previous_cycle(), lagging_phase + 1.)
+      } else {
+        (lfo.get_shaper(), lagging_phase)
+      };
+      let value = lfo.get_shape_value(&shaper, lagging_phase, params);
+      outputs[index] = lfo.render(value, &mut self.slews[index], params);
+    }
+    outputs
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::SpreadOutputs;
+  use crate::{CvInputs, Lfo, Params};
+
+  #[test]
+  fn should_spread_the_phase_of_the_outputs() {
+    let mut lfo = Lfo::new(100.);
+    let mut spread_outputs = SpreadOutputs::new(100.);
+    let mut params = Params::new(100.);
+    params.set(1., 1., 1., 1., 0.);
+    spread_outputs.set(1., 4.);
+
+    let mut outputs = Vec::new();
+    for _ in 0..100 {
+      lfo.process(&mut params, CvInputs::default());
+      outputs.push(spread_outputs.process(&lfo, &params));
+    }
+    for index in 75..100 {
+      assert!((outputs[index][1] - outputs[index - 25][0]).abs() < 0.001);
+      assert!((outputs[index][3] - outputs[index - 75][0]).abs() < 0.001);
+    }
+    assert_eq!(outputs[99][4], 0.);
+  }
+}
@@ -1,7 +1,9 @@
 extern crate lfo;
 extern crate lv2;
-use lfo::{Lfo, Params};
+mod spread;
+use lfo::{CvInputs, Lfo, Outputs, Params, RatioOutputs, ShapeOutputs, SmoothedParam};
 use lv2::prelude::*;
+use spread::DmLFOSpread;
 
 #[derive(PortCollection)]
 struct Ports {
@@ -11,35 +13,164 @@ struct Ports {
   offset: InputPort<InPlaceControl>,
   chance: InputPort<InPlaceControl>,
   output: OutputPort<InPlaceCV>,
+  rate_mode: InputPort<InPlaceControl>,
+  division: InputPort<InPlaceControl>,
+  control: InputPort<AtomPort>,
+  phase: InputPort<InPlaceControl>,
+  reset: InputPort<InPlaceCV>,
+  sync: InputPort<InPlaceCV>,
+  sync_mode: InputPort<InPlaceControl>,
+  fm: InputPort<InPlaceCV>,
+  fm_mode: InputPort<InPlaceControl>,
+  fm_depth: InputPort<InPlaceControl>,
+  depth_cv: InputPort<InPlaceCV>,
+  offset_cv: InputPort<InPlaceCV>,
+  pulse_width: InputPort<InPlaceControl>,
+  pulse_width_cv: InputPort<InPlaceCV>,
+  symmetry: InputPort<InPlaceControl>,
+  shape_morph: InputPort<InPlaceControl>,
+  shape_change: InputPort<InPlaceControl>,
+  rise: InputPort<InPlaceControl>,
+  fall: InputPort<InPlaceControl>,
+  delay: InputPort<InPlaceControl>,
+  fade: InputPort<InPlaceControl>,
+  output_range: InputPort<InPlaceControl>,
+  overflow: InputPort<InPlaceControl>,
+  curve: InputPort<InPlaceControl>,
+  steps: InputPort<InPlaceControl>,
+  step_mode: InputPort<InPlaceControl>,
+  scale: InputPort<InPlaceControl>,
+  root: InputPort<InPlaceControl>,
+  scale_mask: InputPort<InPlaceControl>,
+  trigger: OutputPort<InPlaceCV>,
+  gate_length: InputPort<InPlaceControl>,
+  comparator: OutputPort<InPlaceCV>,
+  threshold: InputPort<InPlaceControl>,
+  hysteresis: InputPort<InPlaceControl>,
+  phase_ramp: OutputPort<InPlaceCV>,
+  sine: OutputPort<InPlaceCV>,
+  triangle: OutputPort<InPlaceCV>,
+  saw: OutputPort<InPlaceCV>,
+  square: OutputPort<InPlaceCV>,
+  ratio_1: InputPort<InPlaceControl>,
+  ratio_output_1: OutputPort<InPlaceCV>,
+  ratio_2: InputPort<InPlaceControl>,
+  ratio_output_2: OutputPort<InPlaceCV>,
+  ratio_3: InputPort<InPlaceControl>,
+  ratio_output_3: OutputPort<InPlaceCV>,
+  ratio_4: InputPort<InPlaceControl>,
+  ratio_output_4: OutputPort<InPlaceCV>,
+  freq_smoothing: InputPort<InPlaceControl>,
+  depth_smoothing: InputPort<InPlaceControl>,
+  offset_smoothing: InputPort<InPlaceControl>,
+  chance_smoothing: InputPort<InPlaceControl>,
+}
+
+#[derive(FeatureCollection)]
+pub struct Features<'a> {
+  map: LV2Map<'a>,
+}
+
+#[derive(URIDCollection)]
+pub struct URIDs {
+  atom: AtomURIDCollection,
+  unit: UnitURIDCollection,
+  time: TimeURIDCollection,
 }
 
 #[uri("https://github.com/davemollen/dm-LFO")]
 struct DmLFO {
   lfo: Lfo,
   params: Params,
+  shape_outputs: ShapeOutputs,
+  ratio_outputs: RatioOutputs,
+  urids: URIDs,
+}
+
+// Read the host's time:Position objects so the tempo synced rate and phase follow the song.
+fn read_time_position(control: &InputPort<AtomPort>, urids: &URIDs, params: &mut Params) {
+  let control_sequence = match control.read(urids.atom.sequence, urids.unit.beat) {
+    Some(sequence_iter) => sequence_iter,
+    None => return,
+  };
+
+  for (_, message) in control_sequence {
+    let (object_header, object_reader) = match message.read(urids.atom.object, ()) {
+      Some(object) => object,
+      None => continue,
+    };
+    if object_header.otype != urids.time.position_class {
+      continue;
+    }
+
+    for (property_header, property) in object_reader {
+      if property_header.key == urids.time.beats_per_minute {
+        if let Some(bpm) = property.read(urids.atom.float, ()) {
+          params.transport.set_bpm(bpm);
+        }
+      } else if property_header.key == urids.time.beat_unit {
+        if let Some(beat_unit) = property.read(urids.atom.int, ()) {
+          params.transport.set_beat_unit(beat_unit as f32);
+        }
+      } else if property_header.key == urids.time.beats_per_bar {
+        if let Some(beats_per_bar) = property.read(urids.atom.float, ()) {
+          params.transport.set_beats_per_bar(beats_per_bar);
+        }
+      } else if property_header.key == urids.time.bar {
+        if let Some(bar) = property.read(urids.atom.long, ()) {
+          params.transport.set_bar(bar as f64);
+        }
+      } else if property_header.key == urids.time.bar_beat {
+        if let Some(bar_beat) = property.read(urids.atom.float, ()) {
+          params.transport.set_bar_beat(bar_beat as f64);
+        }
+      } else if property_header.key == urids.time.speed {
+        if let Some(speed) = property.read(urids.atom.float, ()) {
+          params.transport.set_speed(speed);
+        }
+      }
+    }
+  }
 }
 
 impl Plugin for DmLFO {
   // Tell the framework which ports this plugin has.
   type Ports = Ports;
 
-  // We don't need any special host features; We can leave them out.
-  type InitFeatures = ();
+  // We need the URID map to read the host's time position.
+  type InitFeatures = Features<'static>;
   type AudioFeatures = ();
 
   // Create a new instance of the plugin; Trivial in this case.
-  fn new(plugin_info: &PluginInfo, _features: &mut ()) -> Option<Self> {
+  fn new(plugin_info: &PluginInfo, features: &mut Features<'static>) -> Option<Self> {
     let sample_rate = plugin_info.sample_rate() as f32;
 
     Some(Self {
       lfo: Lfo::new(sample_rate),
       params: Params::new(sample_rate),
+      shape_outputs: ShapeOutputs::new(sample_rate),
+      ratio_outputs: RatioOutputs::new(sample_rate),
+      urids: features.map.populate_collection()?,
     })
   }
 
   // Process a chunk of audio. The audio ports are dereferenced to slices, which the plugin
   // iterates over.
   fn run(&mut self, ports: &mut Ports, _features: &mut (), _sample_count: u32) {
+    read_time_position(&ports.control, &self.urids, &mut self.params);
+
+    self
+      .params
+      .set_smoothing_time(SmoothedParam::Freq, ports.freq_smoothing.get());
+    self
+      .params
+      .set_smoothing_time(SmoothedParam::Depth, ports.depth_smoothing.get());
+    self
+      .params
+      .set_smoothing_time(SmoothedParam::Offset, ports.offset_smoothing.get());
+    self
+      .params
+      .set_smoothing_time(SmoothedParam::Chance, ports.chance_smoothing.get());
     self.params.set(
       ports.freq.get(),
       ports.shape.get(),
@@ -47,12 +178,75 @@ impl Plugin for DmLFO {
       ports.depth.get() * 0.01,
       ports.offset.get() * 0.01,
     );
+    self
+      .params
+      .set_rate(ports.rate_mode.get(), ports.division.get());
+    self.params.set_phase_offset(ports.phase.get() / 360.);
+    self.params.set_sync_mode(ports.sync_mode.get());
+    self
+      .params
+      .set_fm(ports.fm_mode.get(), ports.fm_depth.get() * 0.01);
+    self.params.set_pulse_width(ports.pulse_width.get() * 0.01);
+    self.params.set_symmetry(ports.symmetry.get() * 0.01);
+    self.params.set_shape_morph(ports.shape_morph.get());
+    self.params.set_shape_change(ports.shape_change.get());
+    self.params.set_slew(ports.rise.get(), ports.fall.get());
+    self.params.set_fade_in(ports.delay.get(), ports.fade.get());
+    self.params.set_output_range(ports.output_range.get());
+    self.params.set_overflow(ports.overflow.get());
+    self.params.set_curve(ports.curve.get() * 0.01);
+    self
+      .params
+      .set_steps(ports.steps.get(), ports.step_mode.get());
+    self
+      .params
+      .set_scale(ports.scale.get(), ports.root.get(), ports.scale_mask.get());
+    self.params.set_gate_length(ports.gate_length.get());
+    self
+      .params
+      .set_comparator(ports.threshold.get() * 0.01, ports.hysteresis.get() * 0.01);
+    self.ratio_outputs.set(
+      ports.ratio_1.get(),
+      ports.ratio_2.get(),
+      ports.ratio_3.get(),
+      ports.ratio_4.get(),
+    );
 
-    for output in ports.output.iter() {
-      output.set(self.lfo.process(&mut self.params));
+    for (i, output) in ports.output.iter().enumerate() {
+      let Outputs {
+        output: lfo_output,
+        trigger,
+        comparator,
+        phase_ramp,
+      } = self.lfo.process(
+        &mut self.params,
+        CvInputs {
+          reset: ports.reset[i].get(),
+          sync: ports.sync[i].get(),
+          fm: ports.fm[i].get(),
+          depth: ports.depth_cv[i].get(),
+          offset: ports.offset_cv[i].get(),
+          pulse_width: ports.pulse_width_cv[i].get(),
+        },
+      );
+      output.set(lfo_output);
+      ports.trigger[i].set(trigger);
+      ports.comparator[i].set(comparator);
+      ports.phase_ramp[i].set(phase_ramp);
+      let [sine, triangle, saw, square] = self.shape_outputs.process(&self.lfo, &self.params);
+      ports.sine[i].set(sine);
+      ports.triangle[i].set(triangle);
+      ports.saw[i].set(saw);
+      ports.square[i].set(square);
+      let [ratio_output_1, ratio_output_2, ratio_output_3, ratio_output_4] =
+        self.ratio_outputs.process(&self.lfo, &self.params);
+      ports.ratio_output_1[i].set(ratio_output_1);
+      ports.ratio_output_2[i].set(ratio_output_2);
+      ports.ratio_output_3[i].set(ratio_output_3);
+      ports.ratio_output_4[i].set(ratio_output_4);
     }
   }
 }
 
 // Generate the plugin descriptor function which exports the plugin to the outside world.
-lv2_descriptors!(DmLFO);
+lv2_descriptors!(DmLFO, DmLFOSpread);
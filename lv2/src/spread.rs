@@ -0,0 +1,93 @@
+use crate::{read_time_position, Features, URIDs};
+use lfo::{CvInputs, Lfo, Params, SpreadOutputs};
+use lv2::prelude::*;
+
+#[derive(PortCollection)]
+pub struct Ports {
+  freq: InputPort<InPlaceControl>,
+  depth: InputPort<InPlaceControl>,
+  shape: InputPort<InPlaceControl>,
+  offset: InputPort<InPlaceControl>,
+  chance: InputPort<InPlaceControl>,
+  rate_mode: InputPort<InPlaceControl>,
+  division: InputPort<InPlaceControl>,
+  control: InputPort<AtomPort>,
+  phase: InputPort<InPlaceControl>,
+  reset: InputPort<InPlaceCV>,
+  spread: InputPort<InPlaceControl>,
+  outputs: InputPort<InPlaceControl>,
+  output_1: OutputPort<InPlaceCV>,
+  output_2: OutputPort<InPlaceCV>,
+  output_3: OutputPort<InPlaceCV>,
+  output_4: OutputPort<InPlaceCV>,
+  output_5: OutputPort<InPlaceCV>,
+  output_6: OutputPort<InPlaceCV>,
+  output_7: OutputPort<InPlaceCV>,
+  output_8: OutputPort<InPlaceCV>,
+}
+
+// A companion plugin with phase related copies of the same LFO, sharing one phasor and one set of random decisions.
+#[uri("https://github.com/davemollen/dm-LFO#spread")]
+pub struct DmLFOSpread {
+  lfo: Lfo,
+  params: Params,
+  spread_outputs: SpreadOutputs,
+  urids: URIDs,
+}
+
+impl Plugin for DmLFOSpread {
+  type Ports = Ports;
+
+  type InitFeatures = Features<'static>;
+  type AudioFeatures = ();
+
+  fn new(plugin_info: &PluginInfo, features: &mut Features<'static>) -> Option<Self> {
+    let sample_rate = plugin_info.sample_rate() as f32;
+
+    Some(Self {
+      lfo: Lfo::new(sample_rate),
+      params: Params::new(sample_rate),
+      spread_outputs: SpreadOutputs::new(sample_rate),
+      urids: features.map.populate_collection()?,
+    })
+  }
+
+  fn run(&mut self, ports: &mut Ports, _features: &mut (), _sample_count: u32) {
+    read_time_position(&ports.control, &self.urids, &mut self.params);
+
+    self.params.set(
+      ports.freq.get(),
+      ports.shape.get(),
+      ports.chance.get() * 0.01,
+      ports.depth.get() * 0.01,
+      ports.offset.get() * 0.01,
+    );
+    self
+      .params
+      .set_rate(ports.rate_mode.get(), ports.division.get());
+    self.params.set_phase_offset(ports.phase.get() / 360.);
+    self
+      .spread_outputs
+      .set(ports.spread.get() * 0.01, ports.outputs.get());
+
+    for (i, output_1) in ports.output_1.iter().enumerate() {
+      self.lfo.process(
+        &mut self.params,
+        CvInputs {
+          reset: ports.reset[i].get(),
+          ..Default::default()
+        },
+      );
+      let [out_1, out_2, out_3, out_4, out_5, out_6, out_7, out_8] =
+        self.spread_outputs.process(&self.lfo, &self.params);
+      output_1.set(out_1);
+      ports.output_2[i].set(out_2);
+      ports.output_3[i].set(out_3);
+      ports.output_4[i].set(out_4);
+      ports.output_5[i].set(out_5);
+      ports.output_6[i].set(out_6);
+      ports.output_7[i].set(out_7);
+      ports.output_8[i].set(out_8);
+    }
+  }
+}